@@ -1,5 +1,6 @@
 
 use std::{fs, io};
+use std::io::Write;
 use std::path::{Path, PathBuf};
 use std::marker::PhantomData;
 use std::fmt::Debug;
@@ -8,17 +9,154 @@ extern crate serde;
 use serde::{de::DeserializeOwned, Serialize};
 
 extern crate serde_json;
+extern crate rmp_serde;
+extern crate postcard;
+extern crate bincode;
+extern crate sha2;
+extern crate hex;
+extern crate aes_gcm;
+extern crate chacha20poly1305;
+extern crate argon2;
+extern crate rand;
+extern crate crc32fast;
+
+use std::collections::{HashMap, VecDeque};
+use std::convert::TryInto;
+use sha2::{Digest, Sha256};
+use rand::RngCore;
+use crc32fast::Hasher as Crc32;
+use aes_gcm::Aes256Gcm;
+use aes_gcm::aead::{Aead, KeyInit};
+use chacha20poly1305::ChaCha20Poly1305;
+use argon2::Argon2;
+
+/// Name of the sidecar file mapping hashed file names back to their
+/// original keys, used when `key_mode` is [`KeyMode::Hashed`]
+const KEY_INDEX_FILE: &str = ".key_index.json";
+
+/// Name of the sidecar file holding the per-store Argon2 salt, used when
+/// encryption is enabled via `with_encryption`
+const SALT_FILE: &str = ".salt";
+
+/// Length in bytes of the AEAD nonce written ahead of each encrypted file
+const NONCE_LEN: usize = 12;
+
+/// Controls how keys are mapped onto on-disk file names
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyMode {
+    /// Use the key directly as the file name (default)
+    Raw,
+    /// Store keys that are unsafe or exceed `max_file_name` under a
+    /// hex-encoded SHA-256 hash, recording the original key in a sidecar
+    /// index so `list`/`load_all` can still report the real key
+    Hashed,
+}
+
+/// Selects the AEAD cipher used for encryption-at-rest
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CipherKind {
+    Aes256Gcm = 0,
+    ChaCha20Poly1305 = 1,
+}
+
+/// Transparent encryption-at-rest for a [`FileStore`]
+///
+/// Wraps the already-encoded buffer, so it has no bearing on the
+/// `V`/`Codec` contract: files are written as
+/// `[cipher_id][nonce][ciphertext+tag]`.
+struct Encryption {
+    cipher: CipherKind,
+    key: Vec<u8>,
+}
+
+impl Encryption {
+    fn derive_key(passphrase: &str, salt: &[u8]) -> Result<Vec<u8>, String> {
+        let mut key = [0u8; 32];
+        Argon2::default()
+            .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+            .map_err(|e| format!("key derivation failed: {}", e))?;
+        Ok(key.to_vec())
+    }
+
+    fn encrypt(&self, plaintext: &[u8]) -> Result<Vec<u8>, String> {
+        let mut nonce = [0u8; NONCE_LEN];
+        rand::thread_rng().fill_bytes(&mut nonce);
+
+        let ciphertext = match self.cipher {
+            CipherKind::Aes256Gcm => {
+                let cipher = Aes256Gcm::new_from_slice(&self.key)
+                    .map_err(|e| format!("invalid key: {}", e))?;
+                cipher.encrypt(nonce.as_ref().into(), plaintext)
+                    .map_err(|e| format!("encryption failed: {}", e))?
+            }
+            CipherKind::ChaCha20Poly1305 => {
+                let cipher = ChaCha20Poly1305::new_from_slice(&self.key)
+                    .map_err(|e| format!("invalid key: {}", e))?;
+                cipher.encrypt(nonce.as_ref().into(), plaintext)
+                    .map_err(|e| format!("encryption failed: {}", e))?
+            }
+        };
+
+        let mut out = Vec::with_capacity(1 + NONCE_LEN + ciphertext.len());
+        out.push(self.cipher as u8);
+        out.extend_from_slice(&nonce);
+        out.extend_from_slice(&ciphertext);
+        Ok(out)
+    }
+
+    fn decrypt(&self, buff: &[u8]) -> Result<Vec<u8>, String> {
+        if buff.len() < 1 + NONCE_LEN {
+            return Err("encrypted payload too short".to_string());
+        }
+
+        let cipher_id = buff[0];
+        let nonce = &buff[1..1 + NONCE_LEN];
+        let ciphertext = &buff[1 + NONCE_LEN..];
+
+        match cipher_id {
+            0 => {
+                let cipher = Aes256Gcm::new_from_slice(&self.key)
+                    .map_err(|e| format!("invalid key: {}", e))?;
+                cipher.decrypt(nonce.into(), ciphertext)
+                    .map_err(|e| format!("decryption failed: {}", e))
+            }
+            1 => {
+                let cipher = ChaCha20Poly1305::new_from_slice(&self.key)
+                    .map_err(|e| format!("invalid key: {}", e))?;
+                cipher.decrypt(nonce.into(), ciphertext)
+                    .map_err(|e| format!("decryption failed: {}", e))
+            }
+            other => Err(format!("unknown cipher id {}", other)),
+        }
+    }
+}
 
 /// A simple file system based key:value data store
-pub struct FileStore<V> {
+///
+/// `V` is the value type stored, `C` selects the on-disk [`Codec`]
+/// (defaults to [`JsonCodec`]).
+pub struct FileStore<V, C = JsonCodec> {
     dir: PathBuf,
+    key_mode: KeyMode,
+    max_file_name: Option<usize>,
+    encryption: Option<Encryption>,
     _v: PhantomData<V>,
+    _c: PhantomData<C>,
 }
 
 #[derive(Debug)]
 pub enum Error<E> {
     Io(io::Error),
     Inner(E),
+    /// Key derivation or AEAD encrypt/decrypt failure
+    Crypto(String),
+    /// Stored checksum didn't match the recomputed one for `name`, or the
+    /// file is too short to hold a checksum (then `expected` and `actual`
+    /// are both 0)
+    Corrupt { name: String, expected: u32, actual: u32 },
+    /// Key can't be used as a raw file name (path separators, `.`/`..`, or
+    /// a name reserved for the store's own bookkeeping)
+    InvalidKey(String),
 }
 
 impl <E> From<io::Error> for Error<E> {
@@ -28,65 +166,183 @@ impl <E> From<io::Error> for Error<E> {
 }
 
 
-impl <V, E>FileStore<V> 
+impl <V, C, E>FileStore<V, C>
 where
-    V: EncodeDecode<Value=V, Error=E> + Serialize + DeserializeOwned + Debug,
+    V: Serialize + DeserializeOwned + Debug,
+    C: Codec<V, Error=E>,
     E: Debug
 {
     /// Create a new FileStore
     pub fn new<P: AsRef<Path>>(dir: P) -> Result<Self, Error<E>> {
         Ok(FileStore{
-            dir: dir.as_ref().into(), 
-            _v: PhantomData
+            dir: dir.as_ref().into(),
+            key_mode: KeyMode::Raw,
+            max_file_name: None,
+            encryption: None,
+            _v: PhantomData,
+            _c: PhantomData,
         })
     }
 
-    /// List all files in the database
+    /// Enable hashing of unsafe or overlong keys, optionally capping the
+    /// raw file name length at `max_file_name` bytes
+    pub fn with_hashed_keys(mut self, max_file_name: Option<usize>) -> Self {
+        self.key_mode = KeyMode::Hashed;
+        self.max_file_name = max_file_name;
+        self
+    }
+
+    /// Enable transparent encryption-at-rest, deriving the data key from
+    /// `passphrase` with Argon2 over a per-store random salt (persisted
+    /// once in the store directory)
+    pub fn with_encryption(mut self, passphrase: &str, cipher: CipherKind) -> Result<Self, Error<E>> {
+        let salt = self.load_or_create_salt()?;
+        let key = Encryption::derive_key(passphrase, &salt).map_err(Error::Crypto)?;
+        self.encryption = Some(Encryption { cipher, key });
+        Ok(self)
+    }
+
+    fn load_or_create_salt(&self) -> Result<Vec<u8>, Error<E>> {
+        let path = self.dir.join(SALT_FILE);
+
+        if path.exists() {
+            return Ok(fs::read(path)?);
+        }
+
+        let mut salt = [0u8; 16];
+        rand::thread_rng().fill_bytes(&mut salt);
+        fs::write(path, salt)?;
+        Ok(salt.to_vec())
+    }
+
+    /// Resolve a key to the file name it is (or should be) stored under
+    ///
+    /// Path traversal is always prevented, independent of `key_mode`: an
+    /// unsafe or overlong key is either hashed (in [`KeyMode::Hashed`]) or
+    /// rejected outright (in [`KeyMode::Raw`]) — it is never used as-is.
+    fn resolve_name(&self, key: &str) -> Result<String, Error<E>> {
+        let too_long = self.max_file_name.is_some_and(|max| key.len() > max);
+
+        match self.key_mode {
+            KeyMode::Raw if too_long || !is_safe_key(key) => {
+                Err(Error::InvalidKey(key.to_string()))
+            }
+            KeyMode::Raw => Ok(key.to_string()),
+            KeyMode::Hashed if too_long || !is_safe_key(key) => Ok(hash_key(key)),
+            KeyMode::Hashed => Ok(key.to_string()),
+        }
+    }
+
+    /// Record a hashed-name -> original-key mapping in the sidecar index
+    fn record_key(&self, hashed: &str, key: &str) -> Result<(), Error<E>> {
+        if hashed == key {
+            return Ok(());
+        }
+
+        let mut index = self.load_index()?;
+        index.insert(hashed.to_string(), key.to_string());
+        self.save_index(&index)
+    }
+
+    fn load_index(&self) -> Result<HashMap<String, String>, Error<E>> {
+        let path = self.dir.join(KEY_INDEX_FILE);
+
+        if !path.exists() {
+            return Ok(HashMap::new());
+        }
+
+        let buff = fs::read(path)?;
+        Ok(serde_json::from_slice(&buff).unwrap_or_default())
+    }
+
+    fn save_index(&self, index: &HashMap<String, String>) -> Result<(), Error<E>> {
+        let path = self.dir.join(KEY_INDEX_FILE);
+        let bin = serde_json::to_vec(index).unwrap_or_default();
+        fs::write(path, bin)?;
+        Ok(())
+    }
+
+    /// List all keys in the database
     pub fn list(&mut self) -> Result<Vec<String>, Error<E>> {
+        let index = self.load_index()?;
         let mut names = vec![];
 
         for entry in fs::read_dir(&self.dir)? {
             let entry = entry?;
             let name = entry.file_name().into_string().unwrap();
-            names.push(name);
+
+            if is_reserved_file(&name) {
+                continue;
+            }
+
+            names.push(index.get(&name).cloned().unwrap_or(name));
         }
 
         Ok(names)
     }
 
-    /// Load a file by name
-    pub fn load<P: AsRef<Path>>(&mut self, name: P) -> Result<V, Error<E>> {
-        let mut path = self.dir.clone();
-        path.push(name);
+    /// Load a file by key
+    pub fn load(&mut self, key: &str) -> Result<V, Error<E>> {
+        let name = self.resolve_name(key)?;
+        let path = self.dir.join(&name);
 
-        let buff = fs::read(path)?;
-        let obj: V = V::decode(&buff).map_err(|e| Error::Inner(e) )?;
+        let mut buff = fs::read(path)?;
+        if let Some(enc) = &self.encryption {
+            buff = enc.decrypt(&buff).map_err(Error::Crypto)?;
+        }
+        let payload = unframe_checksum(&name, &buff)?;
+        let obj: V = C::decode(&payload).map_err(|e| Error::Inner(e) )?;
 
         Ok(obj)
     }
 
-    /// Store a file by name
-    pub fn store<P: AsRef<Path>>(&mut self, name: P, v: &V) -> Result<(), Error<E>> {
-        let mut path = self.dir.clone();
-        path.push(name);
-        
-        let bin: Vec<u8> = V::encode(v).map_err(|e| Error::Inner(e) )?;
-        fs::write(path, bin)?;
+    /// Store a file by key
+    pub fn store(&mut self, key: &str, v: &V) -> Result<(), Error<E>> {
+        let name = self.resolve_name(key)?;
+        let path = self.dir.join(&name);
+
+        let encoded: Vec<u8> = C::encode(v).map_err(|e| Error::Inner(e) )?;
+        let mut bin = frame_checksum(&encoded);
+        if let Some(enc) = &self.encryption {
+            bin = enc.encrypt(&bin).map_err(Error::Crypto)?;
+        }
+        atomic_write(&path, &bin)?;
+
+        self.record_key(&name, key)?;
         Ok(())
     }
 
-    /// Load all files from the database
+    /// Load all files from the database, keyed on their original key
+    ///
+    /// Files that fail their checksum are silently skipped rather than
+    /// aborting the whole scan; other errors still abort it.
     pub fn load_all(&mut self) -> Result<Vec<(String, V)>, Error<E>> {
+        let index = self.load_index()?;
         let mut objs = vec![];
 
         for entry in fs::read_dir(&self.dir)? {
             let entry = entry?;
             let name = entry.file_name().into_string().unwrap();
 
-            let buff = fs::read(entry.path())?;
-            let obj: V = V::decode(&buff).map_err(|e| Error::Inner(e) )?;
+            if is_reserved_file(&name) {
+                continue;
+            }
+
+            let mut buff = fs::read(entry.path())?;
+            if let Some(enc) = &self.encryption {
+                buff = enc.decrypt(&buff).map_err(Error::Crypto)?;
+            }
+
+            let payload = match unframe_checksum(&name, &buff) {
+                Ok(payload) => payload,
+                Err(Error::Corrupt { .. }) => continue,
+                Err(e) => return Err(e),
+            };
+
+            let obj: V = C::decode(&payload).map_err(|e| Error::Inner(e) )?;
 
-            objs.push((name, obj));
+            let key = index.get(&name).cloned().unwrap_or(name);
+            objs.push((key, obj));
         }
 
         Ok(objs)
@@ -103,40 +359,398 @@ where
 
 
     /// Remove a file from the database
-    pub fn rm<P: AsRef<Path>>(&mut self, name: P) -> Result<(), Error<E>> {
-        let mut path = self.dir.clone();
-        path.push(name);
+    pub fn rm(&mut self, key: &str) -> Result<(), Error<E>> {
+        let name = self.resolve_name(key)?;
+        let path = self.dir.join(&name);
 
         fs::remove_file(path)?;
 
+        if name != key {
+            let mut index = self.load_index()?;
+            index.remove(&name);
+            self.save_index(&index)?;
+        }
+
         Ok(())
     }
 
 }
 
-/// EncodeDecode trait must be implemented for FileStore types
-pub trait EncodeDecode {
-    type Value;
+/// True for the sidecar files a [`FileStore`] manages itself, which should
+/// never be surfaced as keys by `list`/`load_all`
+fn is_reserved_file(name: &str) -> bool {
+    name == KEY_INDEX_FILE || name == SALT_FILE || is_tmp_file(name)
+}
+
+/// True for the temporary files `atomic_write` leaves behind on disk until
+/// the rename completes (or forever, if the process crashes mid-write)
+fn is_tmp_file(name: &str) -> bool {
+    name.starts_with('.') && name.ends_with(".tmp")
+}
+
+/// A key is safe to use directly as a file name if it is non-empty, has no
+/// path separators, isn't a `.`/`..` special entry, and doesn't collide with
+/// a name the store reserves for its own bookkeeping (see
+/// [`is_reserved_file`]) — otherwise a plain key like `.salt` would silently
+/// overwrite the encryption salt or key index.
+fn is_safe_key(key: &str) -> bool {
+    !key.is_empty()
+        && key != "."
+        && key != ".."
+        && !key.contains('/')
+        && !key.contains('\\')
+        && !is_reserved_file(key)
+}
+
+/// Hex-encoded SHA-256 digest of a key, used as its file name when hashing
+fn hash_key(key: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(key.as_bytes());
+    hex::encode(hasher.finalize())
+}
+
+/// Write `bytes` to `path` without ever leaving a torn file behind
+///
+/// The bytes are written to a temporary file in the same directory,
+/// `fsync`'d, then atomically renamed over `path`, with a best-effort sync
+/// of the directory entry so the rename itself is durable.
+fn atomic_write(path: &Path, bytes: &[u8]) -> io::Result<()> {
+    let dir = path.parent().unwrap_or_else(|| Path::new("."));
+    let file_name = path.file_name().ok_or_else(|| {
+        io::Error::new(io::ErrorKind::InvalidInput, "path has no file name")
+    })?;
+    let tmp_name = format!(".{}.tmp", file_name.to_string_lossy());
+    let tmp_path = dir.join(tmp_name);
+
+    {
+        let mut file = fs::File::create(&tmp_path)?;
+        file.write_all(bytes)?;
+        file.sync_all()?;
+    }
+
+    fs::rename(&tmp_path, path)?;
+
+    if let Ok(dir_file) = fs::File::open(dir) {
+        let _ = dir_file.sync_all();
+    }
+
+    Ok(())
+}
+
+fn crc32(buff: &[u8]) -> u32 {
+    let mut hasher = Crc32::new();
+    hasher.update(buff);
+    hasher.finalize()
+}
+
+/// Prepend a CRC32 of `payload` so corruption can be detected on read
+fn frame_checksum(payload: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(4 + payload.len());
+    out.extend_from_slice(&crc32(payload).to_le_bytes());
+    out.extend_from_slice(payload);
+    out
+}
+
+/// Strip and verify the checksum header written by `frame_checksum`
+fn unframe_checksum<E>(name: &str, buff: &[u8]) -> Result<Vec<u8>, Error<E>> {
+    if buff.len() < 4 {
+        return Err(Error::Corrupt { name: name.to_string(), expected: 0, actual: 0 });
+    }
+
+    let (header, payload) = buff.split_at(4);
+    let expected = u32::from_le_bytes(header.try_into().unwrap());
+    let actual = crc32(payload);
+
+    if expected != actual {
+        return Err(Error::Corrupt { name: name.to_string(), expected, actual });
+    }
+
+    Ok(payload.to_vec())
+}
+
+/// A database root directory that hands out per-type [`FileStore`] buckets
+///
+/// Each bucket is a named subdirectory of the root, created on first open,
+/// so a single `Db` can hold many logically separate collections without
+/// value types colliding on the same directory.
+pub struct Db {
+    root: PathBuf,
+}
+
+impl Db {
+    /// Open (or create) a database rooted at the given directory
+    pub fn new<P: AsRef<Path>>(root: P) -> io::Result<Self> {
+        let root = root.as_ref().to_path_buf();
+        fs::create_dir_all(&root)?;
+        Ok(Db { root })
+    }
+
+    /// Open a bucket scoped to `name`, creating its subdirectory if absent
+    pub fn bucket<V, C, E>(&self, name: &str) -> Result<FileStore<V, C>, Error<E>>
+    where
+        V: Serialize + DeserializeOwned + Debug,
+        C: Codec<V, Error=E>,
+        E: Debug,
+    {
+        let dir = self.root.join(name);
+        fs::create_dir_all(&dir)?;
+        FileStore::new(dir)
+    }
+}
+
+/// Codec selects the on-disk encoding used by a [`FileStore`]
+///
+/// Implementations are zero-sized marker types dispatched via `PhantomData`,
+/// so picking a codec never changes the shape of `FileStore` itself.
+pub trait Codec<V> {
     type Error;
 
-    fn encode(value: &Self::Value) -> Result<Vec<u8>, Self::Error>;
-    fn decode(buff: &[u8]) -> Result<Self::Value, Self::Error>;
+    fn encode(value: &V) -> Result<Vec<u8>, Self::Error>;
+    fn decode(buff: &[u8]) -> Result<V, Self::Error>;
 }
 
-/// Automagic EncodeDecode implementation for serde capable types
-impl <V> EncodeDecode for V
+/// JSON codec, backed by `serde_json`
+pub struct JsonCodec;
+
+impl <V> Codec<V> for JsonCodec
 where
-    V: Serialize + DeserializeOwned + Debug,
+    V: Serialize + DeserializeOwned,
 {
-    type Value = V;
     type Error = serde_json::Error;
 
-    fn encode(value: &Self::Value) -> Result<Vec<u8>, Self::Error> {
+    fn encode(value: &V) -> Result<Vec<u8>, Self::Error> {
         serde_json::to_vec(value)
     }
 
-    fn decode(buff: &[u8]) -> Result<Self::Value, Self::Error> {
-        serde_json::from_slice(&buff)
+    fn decode(buff: &[u8]) -> Result<V, Self::Error> {
+        serde_json::from_slice(buff)
+    }
+}
+
+/// MessagePack codec, backed by `rmp_serde`
+pub struct MsgPackCodec;
+
+/// Error produced by [`MsgPackCodec`]
+///
+/// `rmp_serde` uses distinct error types for encoding and decoding, so this
+/// wraps both rather than forcing a decode failure through the encode
+/// error's shape.
+#[derive(Debug)]
+pub enum MsgPackError {
+    Encode(rmp_serde::encode::Error),
+    Decode(rmp_serde::decode::Error),
+}
+
+impl <V> Codec<V> for MsgPackCodec
+where
+    V: Serialize + DeserializeOwned,
+{
+    type Error = MsgPackError;
+
+    fn encode(value: &V) -> Result<Vec<u8>, Self::Error> {
+        rmp_serde::to_vec(value).map_err(MsgPackError::Encode)
+    }
+
+    fn decode(buff: &[u8]) -> Result<V, Self::Error> {
+        rmp_serde::from_slice(buff).map_err(MsgPackError::Decode)
+    }
+}
+
+/// Postcard codec, backed by `postcard`
+pub struct PostcardCodec;
+
+impl <V> Codec<V> for PostcardCodec
+where
+    V: Serialize + DeserializeOwned,
+{
+    type Error = postcard::Error;
+
+    fn encode(value: &V) -> Result<Vec<u8>, Self::Error> {
+        postcard::to_allocvec(value)
+    }
+
+    fn decode(buff: &[u8]) -> Result<V, Self::Error> {
+        postcard::from_bytes(buff)
+    }
+}
+
+/// Bincode codec, backed by `bincode`
+pub struct BincodeCodec;
+
+impl <V> Codec<V> for BincodeCodec
+where
+    V: Serialize + DeserializeOwned,
+{
+    type Error = bincode::Error;
+
+    fn encode(value: &V) -> Result<Vec<u8>, Self::Error> {
+        bincode::serialize(value)
+    }
+
+    fn decode(buff: &[u8]) -> Result<V, Self::Error> {
+        bincode::deserialize(buff)
+    }
+}
+
+/// Eviction policy for a [`CachedStore`]'s in-memory cache
+#[derive(Debug, Clone, Copy)]
+pub enum EvictionPolicy {
+    /// Never evict; the cache grows to hold every key ever seen
+    Unbounded,
+    /// Evict the least-recently-used entry once the cache holds more than
+    /// `max_entries` keys
+    Lru { max_entries: usize },
+}
+
+struct CacheEntry<V> {
+    value: V,
+    dirty: bool,
+}
+
+/// An in-memory write-back cache in front of a [`FileStore`]
+///
+/// Repeated `load`s of the same key are served from memory instead of
+/// re-reading and re-decoding from disk. Writes land in the cache first and
+/// are flushed to disk in batch by [`CachedStore::flush`] or on drop; under
+/// [`EvictionPolicy::Lru`] a dirty entry is also written to disk when it is
+/// evicted.
+///
+/// The bounds live on the struct itself (rather than only on its impls) so
+/// that the `Drop` impl below is allowed to rely on them.
+pub struct CachedStore<V, C = JsonCodec>
+where
+    V: Serialize + DeserializeOwned + Debug + Clone,
+    C: Codec<V>,
+    C::Error: Debug,
+{
+    store: FileStore<V, C>,
+    policy: EvictionPolicy,
+    entries: HashMap<String, CacheEntry<V>>,
+    lru: VecDeque<String>,
+}
+
+impl <V, C> CachedStore<V, C>
+where
+    V: Serialize + DeserializeOwned + Debug + Clone,
+    C: Codec<V>,
+    C::Error: Debug,
+{
+    /// Wrap a `FileStore` with an unbounded in-memory cache
+    pub fn new(store: FileStore<V, C>) -> Self {
+        CachedStore {
+            store,
+            policy: EvictionPolicy::Unbounded,
+            entries: HashMap::new(),
+            lru: VecDeque::new(),
+        }
+    }
+
+    /// Set the cache's eviction policy
+    pub fn with_eviction_policy(mut self, policy: EvictionPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    fn touch(&mut self, key: &str) {
+        if let EvictionPolicy::Lru { .. } = self.policy {
+            self.lru.retain(|k| k != key);
+            self.lru.push_back(key.to_string());
+        }
+    }
+
+    fn evict_if_needed(&mut self) -> Result<(), Error<C::Error>> {
+        let max_entries = match self.policy {
+            EvictionPolicy::Unbounded => return Ok(()),
+            EvictionPolicy::Lru { max_entries } => max_entries,
+        };
+
+        while self.entries.len() > max_entries {
+            let oldest = match self.lru.pop_front() {
+                Some(key) => key,
+                None => break,
+            };
+
+            if let Some(entry) = self.entries.remove(&oldest) {
+                if entry.dirty {
+                    self.store.store(&oldest, &entry.value)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Load a value, serving it from the cache when present
+    pub fn load(&mut self, key: &str) -> Result<V, Error<C::Error>> {
+        if let Some(entry) = self.entries.get(key) {
+            let value = entry.value.clone();
+            self.touch(key);
+            return Ok(value);
+        }
+
+        let value = self.store.load(key)?;
+        self.entries.insert(key.to_string(), CacheEntry { value: value.clone(), dirty: false });
+        self.touch(key);
+        self.evict_if_needed()?;
+
+        Ok(value)
+    }
+
+    /// Store a value in the cache, deferring the disk write to `flush`
+    /// (or to eviction, under [`EvictionPolicy::Lru`])
+    pub fn store(&mut self, key: &str, v: &V) -> Result<(), Error<C::Error>> {
+        self.entries.insert(key.to_string(), CacheEntry { value: v.clone(), dirty: true });
+        self.touch(key);
+        self.evict_if_needed()?;
+
+        Ok(())
+    }
+
+    /// Remove a value from both the cache and disk
+    ///
+    /// A key that was `store`d but never flushed has no on-disk file yet, so
+    /// a `NotFound` from the underlying store in that case is not an error.
+    pub fn rm(&mut self, key: &str) -> Result<(), Error<C::Error>> {
+        let entry = self.entries.remove(key);
+        self.lru.retain(|k| k != key);
+
+        match self.store.rm(key) {
+            Ok(()) => Ok(()),
+            Err(Error::Io(e)) if e.kind() == io::ErrorKind::NotFound
+                && entry.is_some_and(|entry| entry.dirty) => Ok(()),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Write back any dirty entries to disk
+    pub fn flush(&mut self) -> Result<(), Error<C::Error>> {
+        let dirty: Vec<(String, V)> = self.entries.iter()
+            .filter(|(_, entry)| entry.dirty)
+            .map(|(key, entry)| (key.clone(), entry.value.clone()))
+            .collect();
+
+        for (key, value) in &dirty {
+            self.store.store(key, value)?;
+        }
+
+        for (key, _) in &dirty {
+            if let Some(entry) = self.entries.get_mut(key) {
+                entry.dirty = false;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl <V, C> Drop for CachedStore<V, C>
+where
+    V: Serialize + DeserializeOwned + Debug + Clone,
+    C: Codec<V>,
+    C::Error: Debug,
+{
+    fn drop(&mut self) {
+        let _ = self.flush();
     }
 }
 
@@ -154,7 +768,7 @@ mod tests {
 
         let dir = env::temp_dir();
 
-        let mut s = FileStore::new(dir).unwrap();
+        let mut s: FileStore<usize> = FileStore::new(dir).unwrap();
 
         for i in 0..N {
             let name = format!("{}", i);
@@ -166,4 +780,204 @@ mod tests {
             assert_eq!(i, v);
         }
     }
+
+    #[test]
+    fn mock_database_msgpack() {
+
+        let dir = env::temp_dir();
+
+        let mut s: FileStore<usize, MsgPackCodec> = FileStore::new(dir).unwrap();
+
+        for i in 0..N {
+            let name = format!("msgpack-{}", i);
+
+            s.store(&name, &i).unwrap();
+
+            let v = s.load(&name).unwrap();
+
+            assert_eq!(i, v);
+        }
+    }
+
+    #[test]
+    fn db_buckets() {
+
+        let root = env::temp_dir().join("rust-fs-db-test-buckets");
+
+        let db = Db::new(&root).unwrap();
+
+        let mut users: FileStore<usize> = db.bucket("users").unwrap();
+        let mut posts: FileStore<usize> = db.bucket("posts").unwrap();
+
+        users.store("1", &1).unwrap();
+        posts.store("1", &2).unwrap();
+
+        assert_eq!(users.load("1").unwrap(), 1);
+        assert_eq!(posts.load("1").unwrap(), 2);
+    }
+
+    #[test]
+    fn hashed_keys() {
+
+        let dir = env::temp_dir().join("rust-fs-db-test-hashed-keys");
+        fs::create_dir_all(&dir).unwrap();
+
+        let mut s: FileStore<usize> = FileStore::new(dir).unwrap().with_hashed_keys(Some(8));
+
+        let unsafe_key = "nested/key";
+        let long_key = "a-key-that-is-much-longer-than-the-configured-limit";
+
+        s.store(unsafe_key, &1).unwrap();
+        s.store(long_key, &2).unwrap();
+
+        assert_eq!(s.load(unsafe_key).unwrap(), 1);
+        assert_eq!(s.load(long_key).unwrap(), 2);
+
+        let names = s.list().unwrap();
+        assert!(names.contains(&unsafe_key.to_string()));
+        assert!(names.contains(&long_key.to_string()));
+    }
+
+    #[test]
+    fn rejects_path_traversal() {
+
+        let dir = env::temp_dir().join("rust-fs-db-test-traversal");
+        fs::create_dir_all(&dir).unwrap();
+
+        let mut s: FileStore<usize> = FileStore::new(&dir).unwrap();
+
+        assert!(matches!(s.store("../escaped", &1), Err(Error::InvalidKey(_))));
+        assert!(matches!(s.store("..", &1), Err(Error::InvalidKey(_))));
+        assert!(matches!(s.store("nested/key", &1), Err(Error::InvalidKey(_))));
+
+        assert!(!dir.parent().unwrap().join("escaped").exists());
+    }
+
+    #[test]
+    fn encrypted_store() {
+
+        let dir = env::temp_dir().join("rust-fs-db-test-encrypted");
+        fs::create_dir_all(&dir).unwrap();
+
+        let mut s: FileStore<usize> = FileStore::new(dir)
+            .unwrap()
+            .with_encryption("correct horse battery staple", CipherKind::ChaCha20Poly1305)
+            .unwrap();
+
+        s.store("secret", &42).unwrap();
+
+        assert_eq!(s.load("secret").unwrap(), 42);
+    }
+
+    #[test]
+    fn rejects_sidecar_name_collision() {
+
+        let dir = env::temp_dir().join("rust-fs-db-test-sidecar-collision");
+        fs::create_dir_all(&dir).unwrap();
+
+        let mut s: FileStore<usize> = FileStore::new(dir)
+            .unwrap()
+            .with_encryption("hunter2", CipherKind::Aes256Gcm)
+            .unwrap();
+
+        // A key equal to the salt's sidecar file name must never overwrite it.
+        assert!(matches!(s.store(".salt", &1), Err(Error::InvalidKey(_))));
+        assert!(matches!(s.store(".key_index.json", &1), Err(Error::InvalidKey(_))));
+    }
+
+    #[test]
+    fn detects_corruption() {
+
+        let dir = env::temp_dir().join("rust-fs-db-test-corrupt");
+        fs::create_dir_all(&dir).unwrap();
+
+        let mut s: FileStore<usize> = FileStore::new(&dir).unwrap();
+
+        s.store("a", &1).unwrap();
+        s.store("b", &2).unwrap();
+
+        // Truncate "a" in place to simulate a torn write
+        let path = dir.join("a");
+        let mut bytes = fs::read(&path).unwrap();
+        bytes.truncate(bytes.len() - 1);
+        fs::write(&path, &bytes).unwrap();
+
+        assert!(matches!(s.load("a"), Err(Error::Corrupt { .. })));
+
+        // load_all skips the corrupt file rather than aborting the scan
+        let all = s.load_all().unwrap();
+        assert_eq!(all.len(), 1);
+        assert_eq!(all[0], ("b".to_string(), 2));
+    }
+
+    #[test]
+    fn list_ignores_leftover_tmp_files() {
+
+        let dir = env::temp_dir().join("rust-fs-db-test-tmp-leftover");
+        fs::create_dir_all(&dir).unwrap();
+
+        let mut s: FileStore<usize> = FileStore::new(&dir).unwrap();
+        s.store("a", &1).unwrap();
+
+        // Simulate a crash between atomic_write's temp-file creation and rename.
+        fs::write(dir.join(".b.tmp"), b"partial").unwrap();
+
+        assert_eq!(s.list().unwrap(), vec!["a".to_string()]);
+    }
+
+    #[test]
+    fn cached_store_write_back() {
+
+        let dir = env::temp_dir().join("rust-fs-db-test-cached");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+
+        let inner: FileStore<usize> = FileStore::new(&dir).unwrap();
+        let mut cache = CachedStore::new(inner);
+
+        cache.store("1", &1).unwrap();
+        assert_eq!(cache.load("1").unwrap(), 1);
+
+        // Not flushed yet: reading straight from disk should miss.
+        let mut direct: FileStore<usize> = FileStore::new(&dir).unwrap();
+        assert!(direct.load("1").is_err());
+
+        cache.flush().unwrap();
+        assert_eq!(direct.load("1").unwrap(), 1);
+    }
+
+    #[test]
+    fn cached_store_rm_unflushed() {
+
+        let dir = env::temp_dir().join("rust-fs-db-test-cached-rm-unflushed");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+
+        let inner: FileStore<usize> = FileStore::new(&dir).unwrap();
+        let mut cache = CachedStore::new(inner);
+
+        // Never flushed, so there's no on-disk file to remove yet.
+        cache.store("1", &1).unwrap();
+        cache.rm("1").unwrap();
+
+        assert!(cache.load("1").is_err());
+    }
+
+    #[test]
+    fn cached_store_lru_eviction() {
+
+        let dir = env::temp_dir().join("rust-fs-db-test-cached-lru");
+        fs::create_dir_all(&dir).unwrap();
+
+        let inner: FileStore<usize> = FileStore::new(&dir).unwrap();
+        let mut cache = CachedStore::new(inner)
+            .with_eviction_policy(EvictionPolicy::Lru { max_entries: 1 });
+
+        cache.store("1", &1).unwrap();
+        cache.store("2", &2).unwrap();
+
+        // Evicting "1" must have flushed it to disk rather than dropping it.
+        let mut direct: FileStore<usize> = FileStore::new(&dir).unwrap();
+        assert_eq!(direct.load("1").unwrap(), 1);
+    }
 }